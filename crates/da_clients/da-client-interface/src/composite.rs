@@ -0,0 +1,480 @@
+use std::fmt;
+use std::str::FromStr;
+
+use async_trait::async_trait;
+use color_eyre::eyre::{eyre, WrapErr};
+use color_eyre::Result;
+use serde::{Deserialize, Serialize};
+use starknet::core::types::FieldElement;
+use utils::env_utils::get_env_var;
+
+use crate::{DaClient, DaConfig, DaSubmission, DaVerificationStatus, PermanentDaError};
+
+/// How a [`CompositeDaClient`] uses its two DA clients
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum CompositeMode {
+    /// Every state diff is published to both clients. A publish only succeeds if both
+    /// succeed, and the state diff is only `Verified` once both clients verified it. If only
+    /// one client published the state diff, the error carries a [`PartialPublishError`] with
+    /// that client's submission.
+    Mirror,
+    /// State diffs are published to the primary client. The secondary is only used when
+    /// publishing to the primary fails with a [`PermanentDaError`]. Other errors may be
+    /// transient, with the primary possibly still landing the state diff, so they are returned
+    /// as is. Note that no DA client in this workspace marks its errors as permanent yet, so
+    /// until one does the secondary is never used.
+    Failover,
+}
+
+impl FromStr for CompositeMode {
+    type Err = color_eyre::Report;
+
+    fn from_str(mode: &str) -> Result<Self> {
+        match mode {
+            "mirror" => Ok(CompositeMode::Mirror),
+            "failover" => Ok(CompositeMode::Failover),
+            _ => Err(eyre!("Unsupported composite DA mode {:?}, expected mirror or failover", mode)),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct CompositeDaConfig {
+    pub mode: CompositeMode,
+}
+
+impl CompositeDaConfig {
+    /// Reads the config from the environment, returning an error for a missing or invalid
+    /// `DA_COMPOSITE_MODE`
+    pub fn try_from_env() -> Result<Self> {
+        let mode = get_env_var("DA_COMPOSITE_MODE").wrap_err("Failed to get env var DA_COMPOSITE_MODE")?;
+        Ok(Self { mode: mode.parse().wrap_err("Invalid DA_COMPOSITE_MODE")? })
+    }
+}
+
+impl DaConfig for CompositeDaConfig {
+    fn new_from_env() -> Self {
+        Self::try_from_env().unwrap_or_else(|e| panic!("{:?}", e))
+    }
+}
+
+/// One of the two DA clients of a [`CompositeDaClient`]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Backend {
+    Primary,
+    Secondary,
+}
+
+impl fmt::Display for Backend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Backend::Primary => write!(f, "primary"),
+            Backend::Secondary => write!(f, "secondary"),
+        }
+    }
+}
+
+/// Attached to the error of a mirroring [`CompositeDaClient`] when only one of its DA clients
+/// published the state diff. The orchestrator doesn't retry a job whose processing failed, so
+/// this is the only record of where the data landed. Get it with `downcast_ref`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PartialPublishError {
+    /// The DA client which published the state diff
+    pub published_to: Backend,
+    /// The submission returned by that DA client
+    pub submission: DaSubmission,
+}
+
+impl fmt::Display for PartialPublishError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let failed = match self.published_to {
+            Backend::Primary => Backend::Secondary,
+            Backend::Secondary => Backend::Primary,
+        };
+        write!(
+            f,
+            "State diff was only published to the {} DA client, with external id {}; publishing to the {} DA \
+             client failed",
+            self.published_to,
+            self.submission.external_id(),
+            failed
+        )
+    }
+}
+
+/// External id returned by a [`CompositeDaClient`]. It records the mode the state diff was
+/// published with, so verification doesn't depend on the current config.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+enum CompositeExternalId {
+    Mirror { primary: String, secondary: String },
+    Failover { backend: Backend, external_id: String },
+}
+
+/// A DA client which combines two DA clients, either mirroring every state diff to both
+/// or failing over from the primary to the secondary. See [`CompositeMode`].
+pub struct CompositeDaClient {
+    primary: Box<dyn DaClient>,
+    secondary: Box<dyn DaClient>,
+    mode: CompositeMode,
+}
+
+impl CompositeDaClient {
+    pub fn new(config: CompositeDaConfig, primary: Box<dyn DaClient>, secondary: Box<dyn DaClient>) -> Self {
+        Self { primary, secondary, mode: config.mode }
+    }
+
+    fn backend(&self, backend: Backend) -> &dyn DaClient {
+        match backend {
+            Backend::Primary => self.primary.as_ref(),
+            Backend::Secondary => self.secondary.as_ref(),
+        }
+    }
+}
+
+#[async_trait]
+impl DaClient for CompositeDaClient {
+    /// In mirror mode both publishes run concurrently. If either of them fails the whole
+    /// publish fails, even if the other one succeeded; see [`PartialPublishError`]. The
+    /// submissions of the DA clients holding the data are kept in the details. In mirror mode
+    /// the payload size is the one reported by the primary.
+    async fn publish_state_diff(&self, state_diff: Vec<FieldElement>) -> Result<DaSubmission> {
+        let (external_id, payload_size, details) = match self.mode {
+            CompositeMode::Mirror => {
+                let (primary, secondary) = futures::join!(
+                    self.primary.publish_state_diff(state_diff.clone()),
+                    self.secondary.publish_state_diff(state_diff)
+                );
+                let (primary, secondary) = match (primary, secondary) {
+                    (Ok(primary), Ok(secondary)) => (primary, secondary),
+                    (Ok(submission), Err(error)) => {
+                        return Err(error.wrap_err(PartialPublishError { published_to: Backend::Primary, submission }));
+                    }
+                    (Err(error), Ok(submission)) => {
+                        return Err(
+                            error.wrap_err(PartialPublishError { published_to: Backend::Secondary, submission })
+                        );
+                    }
+                    (Err(primary_error), Err(secondary_error)) => {
+                        return Err(primary_error.wrap_err(format!(
+                            "Failed to publish state diff to both DA clients, the secondary failed with: {}",
+                            secondary_error
+                        )));
+                    }
+                };
+                let details = serde_json::json!({ "primary": &primary, "secondary": &secondary });
+                // both clients got the same state diff, the secondary's size is in the details
+                let payload_size = primary.payload_size;
+                let external_id = CompositeExternalId::Mirror {
+                    primary: primary.into_external_id(),
+                    secondary: secondary.into_external_id(),
+                };
+                (external_id, payload_size, details)
+            }
+            CompositeMode::Failover => {
+                let (backend, submission) = match self.primary.publish_state_diff(state_diff.clone()).await {
+                    Ok(submission) => (Backend::Primary, submission),
+                    Err(primary_error) if PermanentDaError::is_permanent(&primary_error) => {
+                        let submission = self.secondary.publish_state_diff(state_diff).await.wrap_err_with(|| {
+                            format!(
+                                "Failed to publish state diff to the secondary DA client after primary failed: {}",
+                                primary_error
+                            )
+                        })?;
+                        (Backend::Secondary, submission)
+                    }
+                    Err(primary_error) => return Err(primary_error),
+                };
+                let details = serde_json::json!({ "backend": backend, "submission": &submission });
+                let payload_size = submission.payload_size;
+                let external_id = CompositeExternalId::Failover { backend, external_id: submission.into_external_id() };
+                (external_id, payload_size, details)
+            }
+        };
+        Ok(DaSubmission::new("composite", serde_json::to_string(&external_id)?, payload_size).with_details(details))
+    }
+
+    async fn verify_inclusion(&self, external_id: &str) -> Result<DaVerificationStatus> {
+        let external_id: CompositeExternalId = serde_json::from_str(external_id)
+            .wrap_err_with(|| format!("Invalid composite DA external id {}", external_id))?;
+        match external_id {
+            CompositeExternalId::Mirror { primary, secondary } => {
+                let (primary, secondary) = futures::join!(
+                    self.primary.verify_inclusion(&primary),
+                    self.secondary.verify_inclusion(&secondary)
+                );
+                // a rejection is final, even if the other client is temporarily unavailable
+                if let Ok(DaVerificationStatus::Rejected(reason)) = &primary {
+                    return Ok(DaVerificationStatus::Rejected(format!("primary DA client: {}", reason)));
+                }
+                if let Ok(DaVerificationStatus::Rejected(reason)) = &secondary {
+                    return Ok(DaVerificationStatus::Rejected(format!("secondary DA client: {}", reason)));
+                }
+                let primary = primary.wrap_err("Failed to verify inclusion with the primary DA client")?;
+                let secondary = secondary.wrap_err("Failed to verify inclusion with the secondary DA client")?;
+                Ok(match (primary, secondary) {
+                    (DaVerificationStatus::Verified, DaVerificationStatus::Verified) => DaVerificationStatus::Verified,
+                    _ => DaVerificationStatus::Pending,
+                })
+            }
+            CompositeExternalId::Failover { backend, external_id } => {
+                self.backend(backend).verify_inclusion(&external_id).await
+            }
+        }
+    }
+
+    fn max_bytes_per_blob(&self) -> u64 {
+        self.primary.max_bytes_per_blob().min(self.secondary.max_bytes_per_blob())
+    }
+
+    fn max_blob_per_txn(&self) -> u64 {
+        self.primary.max_blob_per_txn().min(self.secondary.max_blob_per_txn())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use color_eyre::eyre::eyre;
+
+    use super::*;
+    use crate::MockDaClient;
+
+    fn client(mode: CompositeMode, primary: MockDaClient, secondary: MockDaClient) -> CompositeDaClient {
+        CompositeDaClient::new(CompositeDaConfig { mode }, Box::new(primary), Box::new(secondary))
+    }
+
+    fn publishing(result: std::result::Result<&'static str, &'static str>) -> MockDaClient {
+        let mut da_client = MockDaClient::new();
+        da_client.expect_publish_state_diff().times(1).returning(move |_| match result {
+            Ok(external_id) => Ok(DaSubmission::new("mock", external_id.to_string(), 32)),
+            Err(message) => Err(eyre!(message)),
+        });
+        da_client
+    }
+
+    fn failing_permanently(reason: &'static str) -> MockDaClient {
+        let mut da_client = MockDaClient::new();
+        da_client.expect_publish_state_diff().times(1).returning(move |_| Err(PermanentDaError::new(reason).into()));
+        da_client
+    }
+
+    fn verifying(expected_external_id: &'static str, status: DaVerificationStatus) -> MockDaClient {
+        let mut da_client = MockDaClient::new();
+        da_client.expect_verify_inclusion().times(1).returning(move |external_id| {
+            assert_eq!(external_id, expected_external_id);
+            Ok(status.clone())
+        });
+        da_client
+    }
+
+    fn rejected(reason: &str) -> DaVerificationStatus {
+        DaVerificationStatus::Rejected(reason.to_string())
+    }
+
+    fn external_id(external_id: &CompositeExternalId) -> String {
+        serde_json::to_string(external_id).unwrap()
+    }
+
+    #[tokio::test]
+    async fn mirror_publishes_to_both() {
+        let da_client = client(CompositeMode::Mirror, publishing(Ok("p")), publishing(Ok("s")));
+        let published = da_client.publish_state_diff(vec![FieldElement::ONE]).await.unwrap();
+        assert_eq!(
+            serde_json::from_str::<CompositeExternalId>(published.external_id()).unwrap(),
+            CompositeExternalId::Mirror { primary: "p".to_string(), secondary: "s".to_string() }
+        );
+        assert_eq!(published.backend, "composite");
+        assert_eq!(published.payload_size, 32);
+        let details = published.details.unwrap();
+        assert_eq!(details["primary"]["external_id"], "p");
+        assert_eq!(details["secondary"]["external_id"], "s");
+    }
+
+    #[tokio::test]
+    async fn mirror_publish_fails_if_either_fails() {
+        let da_client = client(CompositeMode::Mirror, publishing(Ok("p")), publishing(Err("secondary down")));
+        let error = da_client.publish_state_diff(vec![FieldElement::ONE]).await.unwrap_err();
+        assert_eq!(error.root_cause().to_string(), "secondary down");
+        let partial = error.downcast_ref::<PartialPublishError>().expect("error should carry the partial publish");
+        assert_eq!(partial.published_to, Backend::Primary);
+        assert_eq!(partial.submission.external_id(), "p");
+
+        let da_client = client(CompositeMode::Mirror, publishing(Err("primary down")), publishing(Ok("s")));
+        let error = da_client.publish_state_diff(vec![FieldElement::ONE]).await.unwrap_err();
+        assert_eq!(error.root_cause().to_string(), "primary down");
+        let partial = error.downcast_ref::<PartialPublishError>().expect("error should carry the partial publish");
+        assert_eq!(partial.published_to, Backend::Secondary);
+        assert_eq!(partial.submission.external_id(), "s");
+    }
+
+    #[tokio::test]
+    async fn mirror_publish_fails_without_partial_publish_if_both_fail() {
+        let da_client =
+            client(CompositeMode::Mirror, publishing(Err("primary down")), publishing(Err("secondary down")));
+        let error = da_client.publish_state_diff(vec![FieldElement::ONE]).await.unwrap_err();
+        assert!(error.downcast_ref::<PartialPublishError>().is_none());
+        assert!(error.to_string().contains("secondary down"), "unexpected error: {}", error);
+        assert_eq!(error.root_cause().to_string(), "primary down");
+    }
+
+    #[tokio::test]
+    async fn mirror_verification_combines_statuses() {
+        use DaVerificationStatus::{Pending, Verified};
+
+        let cases = [
+            (Verified, Verified, Verified),
+            (Verified, Pending, Pending),
+            (Pending, Verified, Pending),
+            (Pending, Pending, Pending),
+            (rejected("a"), Verified, rejected("primary DA client: a")),
+            (rejected("a"), Pending, rejected("primary DA client: a")),
+            (rejected("a"), rejected("b"), rejected("primary DA client: a")),
+            (Verified, rejected("b"), rejected("secondary DA client: b")),
+            (Pending, rejected("b"), rejected("secondary DA client: b")),
+        ];
+        let mirror_id =
+            external_id(&CompositeExternalId::Mirror { primary: "p".to_string(), secondary: "s".to_string() });
+        for (primary, secondary, expected) in cases {
+            let da_client =
+                client(CompositeMode::Mirror, verifying("p", primary.clone()), verifying("s", secondary.clone()));
+            assert_eq!(
+                da_client.verify_inclusion(&mirror_id).await.unwrap(),
+                expected,
+                "primary {:?}, secondary {:?}",
+                primary,
+                secondary
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn mirror_verification_fails_if_either_errors() {
+        let mut secondary = MockDaClient::new();
+        secondary.expect_verify_inclusion().times(1).returning(|_| Err(eyre!("secondary unreachable")));
+        let da_client = client(CompositeMode::Mirror, verifying("p", DaVerificationStatus::Verified), secondary);
+        let mirror_id =
+            external_id(&CompositeExternalId::Mirror { primary: "p".to_string(), secondary: "s".to_string() });
+        assert!(da_client.verify_inclusion(&mirror_id).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn mirror_rejection_wins_over_verification_error() {
+        let mirror_id =
+            external_id(&CompositeExternalId::Mirror { primary: "p".to_string(), secondary: "s".to_string() });
+
+        let mut secondary = MockDaClient::new();
+        secondary.expect_verify_inclusion().times(1).returning(|_| Err(eyre!("secondary unreachable")));
+        let da_client = client(CompositeMode::Mirror, verifying("p", rejected("a")), secondary);
+        assert_eq!(da_client.verify_inclusion(&mirror_id).await.unwrap(), rejected("primary DA client: a"));
+
+        let mut primary = MockDaClient::new();
+        primary.expect_verify_inclusion().times(1).returning(|_| Err(eyre!("primary unreachable")));
+        let da_client = client(CompositeMode::Mirror, primary, verifying("s", rejected("b")));
+        assert_eq!(da_client.verify_inclusion(&mirror_id).await.unwrap(), rejected("secondary DA client: b"));
+    }
+
+    #[tokio::test]
+    async fn failover_publishes_to_primary_only() {
+        let mut secondary = MockDaClient::new();
+        secondary.expect_publish_state_diff().never();
+        let da_client = client(CompositeMode::Failover, publishing(Ok("p")), secondary);
+        let published = da_client.publish_state_diff(vec![FieldElement::ONE]).await.unwrap();
+        assert_eq!(
+            serde_json::from_str::<CompositeExternalId>(published.external_id()).unwrap(),
+            CompositeExternalId::Failover { backend: Backend::Primary, external_id: "p".to_string() }
+        );
+    }
+
+    #[tokio::test]
+    async fn failover_publishes_to_secondary_when_primary_fails_permanently() {
+        let da_client = client(CompositeMode::Failover, failing_permanently("primary down"), publishing(Ok("s")));
+        let published = da_client.publish_state_diff(vec![FieldElement::ONE]).await.unwrap();
+        assert_eq!(
+            serde_json::from_str::<CompositeExternalId>(published.external_id()).unwrap(),
+            CompositeExternalId::Failover { backend: Backend::Secondary, external_id: "s".to_string() }
+        );
+        let details = published.details.unwrap();
+        assert_eq!(details["backend"], "secondary");
+        assert_eq!(details["submission"]["external_id"], "s");
+    }
+
+    #[tokio::test]
+    async fn failover_returns_transient_primary_errors() {
+        let mut secondary = MockDaClient::new();
+        secondary.expect_publish_state_diff().never();
+        let da_client = client(CompositeMode::Failover, publishing(Err("primary timed out")), secondary);
+        let error = da_client.publish_state_diff(vec![FieldElement::ONE]).await.unwrap_err();
+        assert_eq!(error.to_string(), "primary timed out");
+    }
+
+    #[tokio::test]
+    async fn failover_publish_fails_when_both_fail() {
+        let da_client =
+            client(CompositeMode::Failover, failing_permanently("primary down"), publishing(Err("secondary down")));
+        let error = da_client.publish_state_diff(vec![FieldElement::ONE]).await.unwrap_err();
+        assert!(error.to_string().contains("primary down"), "unexpected error: {}", error);
+    }
+
+    #[tokio::test]
+    async fn failover_verification_uses_recorded_backend() {
+        for status in [DaVerificationStatus::Pending, DaVerificationStatus::Verified, rejected("a")] {
+            let mut secondary = MockDaClient::new();
+            secondary.expect_verify_inclusion().never();
+            let da_client = client(CompositeMode::Failover, verifying("p", status.clone()), secondary);
+            let failover_id =
+                external_id(&CompositeExternalId::Failover { backend: Backend::Primary, external_id: "p".to_string() });
+            assert_eq!(da_client.verify_inclusion(&failover_id).await.unwrap(), status);
+
+            let mut primary = MockDaClient::new();
+            primary.expect_verify_inclusion().never();
+            let da_client = client(CompositeMode::Failover, primary, verifying("s", status.clone()));
+            let failover_id = external_id(&CompositeExternalId::Failover {
+                backend: Backend::Secondary,
+                external_id: "s".to_string(),
+            });
+            assert_eq!(da_client.verify_inclusion(&failover_id).await.unwrap(), status);
+        }
+    }
+
+    #[tokio::test]
+    async fn verification_follows_external_id_not_config() {
+        // published in mirror mode, verified after the config switched to failover
+        let da_client = client(
+            CompositeMode::Failover,
+            verifying("p", DaVerificationStatus::Verified),
+            verifying("s", DaVerificationStatus::Verified),
+        );
+        let mirror_id =
+            external_id(&CompositeExternalId::Mirror { primary: "p".to_string(), secondary: "s".to_string() });
+        assert_eq!(da_client.verify_inclusion(&mirror_id).await.unwrap(), DaVerificationStatus::Verified);
+    }
+
+    #[tokio::test]
+    async fn invalid_external_id_is_an_error() {
+        let da_client = client(CompositeMode::Mirror, MockDaClient::new(), MockDaClient::new());
+        let error = da_client.verify_inclusion("0xdeadbeef").await.unwrap_err();
+        assert!(error.to_string().contains("0xdeadbeef"), "unexpected error: {}", error);
+    }
+
+    #[test]
+    fn parses_mode() {
+        assert_eq!("mirror".parse::<CompositeMode>().unwrap(), CompositeMode::Mirror);
+        assert_eq!("failover".parse::<CompositeMode>().unwrap(), CompositeMode::Failover);
+        let error = "both".parse::<CompositeMode>().unwrap_err().to_string();
+        assert!(error.contains("both"), "unexpected error: {}", error);
+    }
+
+    #[test]
+    fn max_sizes_are_the_smaller_of_both() {
+        let mut primary = MockDaClient::new();
+        primary.expect_max_bytes_per_blob().returning(|| 100);
+        primary.expect_max_blob_per_txn().returning(|| 2);
+        let mut secondary = MockDaClient::new();
+        secondary.expect_max_bytes_per_blob().returning(|| 50);
+        secondary.expect_max_blob_per_txn().returning(|| 6);
+        let da_client = client(CompositeMode::Mirror, primary, secondary);
+        assert_eq!(da_client.max_bytes_per_blob(), 50);
+        assert_eq!(da_client.max_blob_per_txn(), 2);
+    }
+}
@@ -1,27 +1,127 @@
+use std::fmt;
+use std::time::{SystemTime, UNIX_EPOCH};
+
 use async_trait::async_trait;
-use color_eyre::Result;
+use color_eyre::{Report, Result};
 use mockall::{automock, predicate::*};
+use serde::{Deserialize, Serialize};
 use starknet::core::types::FieldElement;
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+/// Combines two DA clients for redundancy
+pub mod composite;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum DaVerificationStatus {
     #[allow(dead_code)]
     Pending,
     #[allow(dead_code)]
     Verified,
+    /// Carries the reason the DA layer rejected the state diff
     #[allow(dead_code)]
-    Rejected,
+    Rejected(String),
+}
+
+/// Marks a DA client error as permanent: retrying the same call can't succeed, e.g. because
+/// the DA layer refused the payload. Clients return it as the error itself or attach it to
+/// an existing error with `wrap_err`. Errors without it may be transient.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PermanentDaError(pub String);
+
+impl PermanentDaError {
+    /// Create a permanent error with the given reason
+    pub fn new(reason: impl Into<String>) -> Self {
+        Self(reason.into())
+    }
+
+    /// Returns whether `error` was marked as permanent
+    pub fn is_permanent(error: &Report) -> bool {
+        error.downcast_ref::<PermanentDaError>().is_some()
+    }
+}
+
+impl fmt::Display for PermanentDaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "permanent DA error: {}", self.0)
+    }
+}
+
+impl std::error::Error for PermanentDaError {}
+
+/// Returns the size of a state diff in bytes, each field element taking 32 bytes
+pub fn state_diff_size(state_diff: &[FieldElement]) -> u64 {
+    state_diff.len() as u64 * 32
+}
+
+/// Handle returned by [`DaClient::publish_state_diff`] describing a published state diff
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DaSubmission {
+    /// Id used to track the state diff on the DA layer. This is what gets persisted as the
+    /// job's external id. Read it with [`DaSubmission::external_id`].
+    external_id: String,
+    /// Name of the DA backend the state diff was published to, e.g. `ethereum`
+    pub backend: String,
+    /// Size of the published payload in bytes
+    pub payload_size: u64,
+    /// When the state diff was submitted, in milliseconds since the unix epoch
+    pub submitted_at_ms: u64,
+    /// Backend specific details about the submission
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub details: Option<serde_json::Value>,
+}
+
+impl DaSubmission {
+    /// Create a submission for a state diff submitted just now, without details
+    pub fn new(backend: &str, external_id: String, payload_size: u64) -> Self {
+        let submitted_at_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| u64::try_from(elapsed.as_millis()).unwrap_or(u64::MAX))
+            .unwrap_or_default();
+        Self { external_id, backend: backend.to_string(), payload_size, submitted_at_ms, details: None }
+    }
+
+    /// Attach backend specific details to the submission
+    pub fn with_details(mut self, details: serde_json::Value) -> Self {
+        self.details = Some(details);
+        self
+    }
+
+    /// Returns the id used to track the state diff on the DA layer
+    pub fn external_id(&self) -> &str {
+        &self.external_id
+    }
+
+    /// Consumes the submission, returning its external id
+    pub fn into_external_id(self) -> String {
+        self.external_id
+    }
 }
 
 /// Trait for every new DaClient to implement
 #[automock]
 #[async_trait]
 pub trait DaClient: Send + Sync {
-    /// Should publish the state diff to the DA layer and return an external id
-    /// which can be used to track the status of the DA transaction.
-    async fn publish_state_diff(&self, state_diff: Vec<FieldElement>) -> Result<String>;
+    /// Should publish the state diff to the DA layer and return a submission whose external id
+    /// can be used to track the status of the DA transaction.
+    async fn publish_state_diff(&self, state_diff: Vec<FieldElement>) -> Result<DaSubmission>;
     /// Should verify the inclusion of the state diff in the DA layer and return the status
     async fn verify_inclusion(&self, external_id: &str) -> Result<DaVerificationStatus>;
+    /// Should verify the inclusion of multiple state diffs in the DA layer and return one
+    /// result per external id, in the same order as `external_ids`. A failed check only fails
+    /// its own entry, and as with `verify_inclusion` an error is not a rejection: it may be
+    /// transient, so the entry should be checked again later. The outer error is for failures
+    /// of the whole batch. The default implementation calls `verify_inclusion` for each id
+    /// sequentially; clients that can do better should override it.
+    async fn verify_inclusions(&self, external_ids: &[String]) -> Result<Vec<Result<DaVerificationStatus>>> {
+        let mut statuses = Vec::with_capacity(external_ids.len());
+        for external_id in external_ids {
+            statuses.push(self.verify_inclusion(external_id).await);
+        }
+        Ok(statuses)
+    }
+    /// Should return the maximum number of bytes that fit in a single blob
+    fn max_bytes_per_blob(&self) -> u64;
+    /// Should return the maximum number of blobs that can be submitted in a single transaction
+    fn max_blob_per_txn(&self) -> u64;
 }
 
 /// Trait for every new DaConfig to implement
@@ -29,3 +129,138 @@ pub trait DaConfig {
     /// Should create a new instance of the DaConfig from the environment variables
     fn new_from_env() -> Self;
 }
+
+#[cfg(test)]
+mod tests {
+    use color_eyre::eyre::{eyre, WrapErr};
+
+    use super::*;
+
+    struct StaticDaClient;
+
+    #[async_trait]
+    impl DaClient for StaticDaClient {
+        async fn publish_state_diff(&self, state_diff: Vec<FieldElement>) -> Result<DaSubmission> {
+            Ok(DaSubmission::new("static", "verified".to_string(), state_diff_size(&state_diff))
+                .with_details(serde_json::json!({ "elements": state_diff.len() })))
+        }
+
+        async fn verify_inclusion(&self, external_id: &str) -> Result<DaVerificationStatus> {
+            match external_id {
+                "pending" => Ok(DaVerificationStatus::Pending),
+                "verified" => Ok(DaVerificationStatus::Verified),
+                "rejected" => Ok(DaVerificationStatus::Rejected("blob failed".to_string())),
+                _ => Err(eyre!("malformed external id {}", external_id)),
+            }
+        }
+
+        fn max_bytes_per_blob(&self) -> u64 {
+            131072
+        }
+
+        fn max_blob_per_txn(&self) -> u64 {
+            6
+        }
+    }
+
+    /// Maps errors to their message so results can be compared
+    fn messages(results: Vec<Result<DaVerificationStatus>>) -> Vec<std::result::Result<DaVerificationStatus, String>> {
+        results.into_iter().map(|result| result.map_err(|e| e.to_string())).collect()
+    }
+
+    #[tokio::test]
+    async fn verify_inclusions_preserves_order() {
+        let external_ids = ["verified", "pending", "rejected", "pending"].map(String::from);
+        let statuses = StaticDaClient.verify_inclusions(&external_ids).await.unwrap();
+        assert_eq!(
+            messages(statuses),
+            vec![
+                Ok(DaVerificationStatus::Verified),
+                Ok(DaVerificationStatus::Pending),
+                Ok(DaVerificationStatus::Rejected("blob failed".to_string())),
+                Ok(DaVerificationStatus::Pending),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn verify_inclusions_fails_failed_entries_only() {
+        let external_ids = ["verified", "garbage", "pending"].map(String::from);
+        let statuses = StaticDaClient.verify_inclusions(&external_ids).await.unwrap();
+        assert_eq!(
+            messages(statuses),
+            vec![
+                Ok(DaVerificationStatus::Verified),
+                Err("malformed external id garbage".to_string()),
+                Ok(DaVerificationStatus::Pending),
+            ]
+        );
+    }
+
+    #[test]
+    fn permanent_errors_are_detected() {
+        assert!(PermanentDaError::is_permanent(&PermanentDaError::new("payload too large").into()));
+        let wrapped: Result<()> = Err(eyre!("status 400")).wrap_err(PermanentDaError::new("payload too large"));
+        assert!(PermanentDaError::is_permanent(&wrapped.unwrap_err()));
+        assert!(!PermanentDaError::is_permanent(&eyre!("connection reset")));
+    }
+
+    #[tokio::test]
+    async fn verify_inclusions_empty_batch() {
+        assert!(StaticDaClient.verify_inclusions(&[]).await.unwrap().is_empty());
+    }
+
+    fn now_ms() -> u64 {
+        SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_millis() as u64
+    }
+
+    #[test]
+    fn new_submission_is_timestamped_without_details() {
+        let before = now_ms();
+        let submission = DaSubmission::new("ethereum", "0xabc".to_string(), 64);
+        let after = now_ms();
+
+        assert_eq!(submission.external_id(), "0xabc");
+        assert_eq!(submission.backend, "ethereum");
+        assert_eq!(submission.payload_size, 64);
+        assert!((before..=after).contains(&submission.submitted_at_ms), "unexpected timestamp");
+        assert_eq!(submission.details, None);
+    }
+
+    #[test]
+    fn with_details_attaches_details() {
+        let submission = DaSubmission::new("ethereum", "0xabc".to_string(), 64);
+        let submitted_at_ms = submission.submitted_at_ms;
+        let submission = submission.with_details(serde_json::json!({ "tx_hash": "0xdef" }));
+
+        assert_eq!(submission.details, Some(serde_json::json!({ "tx_hash": "0xdef" })));
+        assert_eq!(submission.submitted_at_ms, submitted_at_ms, "details should not change the timestamp");
+    }
+
+    #[test]
+    fn state_diff_size_counts_32_bytes_per_element() {
+        assert_eq!(state_diff_size(&[]), 0);
+        assert_eq!(state_diff_size(&[FieldElement::ONE, FieldElement::TWO]), 64);
+    }
+
+    #[test]
+    fn submission_serde_round_trip() {
+        let submission = DaSubmission {
+            external_id: "0xabc".to_string(),
+            backend: "ethereum".to_string(),
+            payload_size: 131072,
+            submitted_at_ms: 1_700_000_000_000,
+            details: Some(serde_json::json!({ "tx_hash": "0xdef", "blobs": 1 })),
+        };
+        let json = serde_json::to_string(&submission).unwrap();
+        assert_eq!(serde_json::from_str::<DaSubmission>(&json).unwrap(), submission);
+    }
+
+    #[test]
+    fn submission_without_details_round_trips() {
+        let submission = DaSubmission::new("ethereum", "0xabc".to_string(), 32);
+        let json = serde_json::to_value(&submission).unwrap();
+        assert!(json.get("details").is_none(), "details should be omitted: {}", json);
+        assert_eq!(serde_json::from_value::<DaSubmission>(json).unwrap(), submission);
+    }
+}
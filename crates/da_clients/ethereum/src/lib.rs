@@ -10,9 +10,15 @@ use std::str::FromStr;
 use url::Url;
 
 use config::EthereumDaConfig;
-use da_client_interface::{DaClient, DaVerificationStatus};
+use da_client_interface::{DaClient, DaSubmission, DaVerificationStatus};
 
 pub mod config;
+
+/// Size of an EIP-4844 blob: 4096 field elements of 32 bytes each
+pub const BLOB_SIZE_BYTES: u64 = 131072;
+/// Maximum number of blobs that can be attached to a single transaction
+pub const MAX_BLOBS_PER_TXN: u64 = 6;
+
 pub struct EthereumDaClient {
     #[allow(dead_code)]
     provider: RpcClient<Http<Client>>,
@@ -20,13 +26,21 @@ pub struct EthereumDaClient {
 
 #[async_trait]
 impl DaClient for EthereumDaClient {
-    async fn publish_state_diff(&self, _state_diff: Vec<FieldElement>) -> Result<String> {
+    async fn publish_state_diff(&self, _state_diff: Vec<FieldElement>) -> Result<DaSubmission> {
         unimplemented!()
     }
 
     async fn verify_inclusion(&self, _external_id: &str) -> Result<DaVerificationStatus> {
         todo!()
     }
+
+    fn max_bytes_per_blob(&self) -> u64 {
+        BLOB_SIZE_BYTES
+    }
+
+    fn max_blob_per_txn(&self) -> u64 {
+        MAX_BLOBS_PER_TXN
+    }
 }
 
 impl From<EthereumDaConfig> for EthereumDaClient {
@@ -3,7 +3,10 @@ use crate::database::mongodb::MongoDb;
 use crate::database::{Database, DatabaseConfig};
 use crate::queue::sqs::SqsQueue;
 use crate::queue::QueueProvider;
-use crate::utils::env_utils::get_env_var_or_panic;
+use crate::utils::env_utils::{get_env_var, get_env_var_or_panic};
+use color_eyre::eyre::{eyre, WrapErr};
+use color_eyre::Result;
+use da_client_interface::composite::{CompositeDaClient, CompositeDaConfig};
 use da_client_interface::DaClient;
 use da_client_interface::DaConfig;
 use dotenvy::dotenv;
@@ -86,13 +89,177 @@ pub async fn config() -> &'static Config {
     CONFIG.get_or_init(init_config).await
 }
 
+/// DA layers which can be selected with the environment variable DA_LAYER
+pub const SUPPORTED_DA_LAYERS: &[&str] = &["ethereum", "composite"];
+
 /// Builds the DA client based on the environment variable DA_LAYER
-fn build_da_client() -> Box<dyn DaClient + Send + Sync> {
-    match get_env_var_or_panic("DA_LAYER").as_str() {
+pub fn build_da_client() -> Box<dyn DaClient + Send + Sync> {
+    build_da_client_for_layer(get_env_var_or_panic("DA_LAYER").as_str()).expect("Failed to build DA client")
+}
+
+/// Builds the DA client for the given DA layer, reading the layer's config from the environment
+pub fn build_da_client_for_layer(da_layer: &str) -> Result<Box<dyn DaClient + Send + Sync>> {
+    match da_layer {
         "ethereum" => {
             let config = EthereumDaConfig::new_from_env();
-            Box::new(EthereumDaClient::from(config))
+            Ok(Box::new(EthereumDaClient::from(config)))
+        }
+        "composite" => Ok(Box::new(build_composite_da_client(build_da_client_for_layer)?)),
+        _ => {
+            Err(eyre!("Unsupported DA layer {:?}, supported layers are: {}", da_layer, SUPPORTED_DA_LAYERS.join(", ")))
+        }
+    }
+}
+
+/// Builds the composite DA client from the environment, using `build_backend` to build its
+/// DA clients from their DA layers
+fn build_composite_da_client(
+    build_backend: impl Fn(&str) -> Result<Box<dyn DaClient + Send + Sync>>,
+) -> Result<CompositeDaClient> {
+    let config = CompositeDaConfig::try_from_env()?;
+    let primary = build_composite_backend("DA_COMPOSITE_PRIMARY_LAYER", &build_backend)?;
+    let secondary = build_composite_backend("DA_COMPOSITE_SECONDARY_LAYER", &build_backend)?;
+    Ok(CompositeDaClient::new(config, primary, secondary))
+}
+
+/// Builds one of the DA clients of the composite DA client from the DA layer in `env_var`
+fn build_composite_backend(
+    env_var: &str,
+    build_backend: impl Fn(&str) -> Result<Box<dyn DaClient + Send + Sync>>,
+) -> Result<Box<dyn DaClient + Send + Sync>> {
+    let da_layer = get_env_var(env_var).wrap_err_with(|| format!("Failed to get env var {}", env_var))?;
+    if da_layer == "composite" {
+        return Err(eyre!("{} can't be composite, composite DA clients can't be nested", env_var));
+    }
+    build_backend(&da_layer)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use starknet::core::types::FieldElement;
+
+    use super::*;
+    use crate::tests::common::in_memory_da_client::InMemoryDaClient;
+
+    /// Serialises the tests which change the composite DA env vars
+    static COMPOSITE_ENV: Mutex<()> = Mutex::new(());
+
+    fn build_error(da_layer: &str) -> String {
+        match build_da_client_for_layer(da_layer) {
+            Ok(_) => panic!("building {} should fail", da_layer),
+            Err(e) => format!("{:?}", e),
+        }
+    }
+
+    #[test]
+    fn builds_ethereum_da_client() {
+        std::env::set_var("ETHEREUM_RPC_URL", "http://localhost:8545");
+        std::env::set_var("MEMORY_PAGES_CONTRACT_ADDRESS", "0x000000000000000000000000000000000000dead");
+        let da_client = build_da_client_for_layer("ethereum").expect("Failed to build ethereum DA client");
+        assert_eq!(da_client.max_bytes_per_blob(), ethereum_da_client::BLOB_SIZE_BYTES);
+        assert_eq!(da_client.max_blob_per_txn(), ethereum_da_client::MAX_BLOBS_PER_TXN);
+    }
+
+    #[tokio::test]
+    async fn all_supported_da_layers_are_buildable() {
+        let da_clients = {
+            let _env = COMPOSITE_ENV.lock().unwrap_or_else(|e| e.into_inner());
+            std::env::set_var("ETHEREUM_RPC_URL", "http://localhost:8545");
+            std::env::set_var("MEMORY_PAGES_CONTRACT_ADDRESS", "0x000000000000000000000000000000000000dead");
+            std::env::set_var("DA_COMPOSITE_MODE", "mirror");
+            std::env::set_var("DA_COMPOSITE_PRIMARY_LAYER", "ethereum");
+            std::env::set_var("DA_COMPOSITE_SECONDARY_LAYER", "ethereum");
+            SUPPORTED_DA_LAYERS
+                .iter()
+                .map(|da_layer| {
+                    let da_client = build_da_client_for_layer(da_layer)
+                        .unwrap_or_else(|e| panic!("{} should be buildable: {:?}", da_layer, e));
+                    (*da_layer, da_client)
+                })
+                .collect::<Vec<_>>()
+        };
+
+        for (da_layer, da_client) in da_clients {
+            match da_layer {
+                "ethereum" => {
+                    assert_eq!(da_client.max_bytes_per_blob(), ethereum_da_client::BLOB_SIZE_BYTES);
+                }
+                "composite" => {
+                    // only the composite client parses its external ids as JSON
+                    let error = da_client.verify_inclusion("0xdeadbeef").await.unwrap_err().to_string();
+                    assert!(error.contains("composite"), "unexpected error: {}", error);
+                }
+                _ => panic!("no expectation for DA layer {}", da_layer),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn composite_da_layer_uses_configured_backends_and_mode() {
+        let build_backend = |da_layer: &str| -> Result<Box<dyn DaClient + Send + Sync>> {
+            match da_layer {
+                "small" => Ok(Box::new(InMemoryDaClient::new().with_blob_limits(1000, 6))),
+                "large" => Ok(Box::new(InMemoryDaClient::new().with_blob_limits(2000, 2))),
+                _ => Err(eyre!("unexpected DA layer {}", da_layer)),
+            }
+        };
+        let da_clients = {
+            let _env = COMPOSITE_ENV.lock().unwrap_or_else(|e| e.into_inner());
+            std::env::set_var("DA_COMPOSITE_PRIMARY_LAYER", "small");
+            std::env::set_var("DA_COMPOSITE_SECONDARY_LAYER", "large");
+            ["mirror", "failover"].map(|mode| {
+                std::env::set_var("DA_COMPOSITE_MODE", mode);
+                (mode, build_composite_da_client(build_backend).unwrap())
+            })
+        };
+
+        for (mode, da_client) in da_clients {
+            assert_eq!(da_client.max_bytes_per_blob(), 1000);
+            assert_eq!(da_client.max_blob_per_txn(), 2);
+            let submission = da_client.publish_state_diff(vec![FieldElement::ONE]).await.unwrap();
+            let external_id: serde_json::Value = serde_json::from_str(submission.external_id()).unwrap();
+            assert_eq!(external_id["mode"], mode);
         }
-        _ => panic!("Unsupported DA layer"),
+    }
+
+    #[test]
+    fn unknown_da_layer_lists_supported_layers() {
+        let error = match build_da_client_for_layer("celestia") {
+            Ok(_) => panic!("celestia should not be a supported DA layer"),
+            Err(e) => e.to_string(),
+        };
+        assert!(error.contains("celestia"), "unexpected error: {}", error);
+        assert!(error.contains("ethereum"), "unexpected error: {}", error);
+    }
+
+    #[test]
+    fn composite_da_clients_cannot_be_nested() {
+        std::env::set_var("ORCHESTRATOR_TEST_NESTED_LAYER", "composite");
+        let error = match build_composite_backend("ORCHESTRATOR_TEST_NESTED_LAYER", build_da_client_for_layer) {
+            Ok(_) => panic!("nested composite DA clients should be rejected"),
+            Err(e) => e.to_string(),
+        };
+        assert!(error.contains("ORCHESTRATOR_TEST_NESTED_LAYER"), "unexpected error: {}", error);
+    }
+
+    #[test]
+    fn composite_config_errors_are_returned() {
+        let _env = COMPOSITE_ENV.lock().unwrap_or_else(|e| e.into_inner());
+        std::env::set_var("ETHEREUM_RPC_URL", "http://localhost:8545");
+        std::env::set_var("MEMORY_PAGES_CONTRACT_ADDRESS", "0x000000000000000000000000000000000000dead");
+
+        std::env::set_var("DA_COMPOSITE_MODE", "both");
+        std::env::set_var("DA_COMPOSITE_PRIMARY_LAYER", "ethereum");
+        std::env::set_var("DA_COMPOSITE_SECONDARY_LAYER", "ethereum");
+        assert!(build_error("composite").contains("DA_COMPOSITE_MODE"));
+
+        std::env::remove_var("DA_COMPOSITE_MODE");
+        assert!(build_error("composite").contains("DA_COMPOSITE_MODE"));
+
+        std::env::set_var("DA_COMPOSITE_MODE", "mirror");
+        std::env::remove_var("DA_COMPOSITE_SECONDARY_LAYER");
+        assert!(build_error("composite").contains("DA_COMPOSITE_SECONDARY_LAYER"));
     }
 }
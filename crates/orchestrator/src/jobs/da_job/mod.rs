@@ -4,6 +4,8 @@ use crate::jobs::Job;
 use async_trait::async_trait;
 use color_eyre::eyre::eyre;
 use color_eyre::Result;
+use da_client_interface::composite::PartialPublishError;
+use da_client_interface::state_diff_size;
 use starknet::core::types::{BlockId, FieldElement, MaybePendingStateUpdate, StateUpdate, StorageEntry};
 use starknet::providers::Provider;
 use std::collections::HashMap;
@@ -48,9 +50,55 @@ impl Job for DaJob {
         };
 
         let blob_data = state_update_to_blob_data(block_no, state_update);
-        let external_id = config.da_client().publish_state_diff(blob_data).await?;
 
-        Ok(external_id)
+        let max_bytes_per_blob = config.da_client().max_bytes_per_blob();
+        let max_blob_per_txn = config.da_client().max_blob_per_txn();
+        if max_bytes_per_blob == 0 {
+            return Err(eyre!("DA client reported a max blob size of 0 bytes"));
+        }
+        let blobs_needed = blobs_required(&blob_data, max_bytes_per_blob);
+        if blobs_needed > max_blob_per_txn {
+            log::error!(
+                "Block {} for job id {} needs {} blobs but the DA client allows at most {} per transaction",
+                block_no,
+                job.id,
+                blobs_needed,
+                max_blob_per_txn
+            );
+            return Err(eyre!(
+                "Block {} for job id {} needs {} blobs but the DA client allows at most {} per transaction",
+                block_no,
+                job.id,
+                blobs_needed,
+                max_blob_per_txn
+            ));
+        }
+
+        let submission = config.da_client().publish_state_diff(blob_data).await.map_err(|e| {
+            // the job isn't retried, so this is where operators find the data which did land
+            if let Some(partial) = e.downcast_ref::<PartialPublishError>() {
+                log::error!(
+                    "Block {} for job id {} was only partially published and needs manual recovery: {}",
+                    block_no,
+                    job.id,
+                    partial
+                );
+            }
+            e
+        })?;
+        log::info!(
+            "Published state diff of block {} for job id {} to {}: external id {}, {} bytes, submitted at {} ms, \
+             details {}",
+            block_no,
+            job.id,
+            submission.backend,
+            submission.external_id(),
+            submission.payload_size,
+            submission.submitted_at_ms,
+            submission.details.as_ref().map(|details| details.to_string()).unwrap_or_else(|| "none".to_string())
+        );
+
+        Ok(submission.into_external_id())
     }
 
     async fn verify_job(&self, config: &Config, job: &JobItem) -> Result<JobVerificationStatus> {
@@ -142,6 +190,11 @@ fn state_update_to_blob_data(block_no: u64, state_update: StateUpdate) -> Vec<Fi
     blob_data
 }
 
+/// Returns the number of blobs needed to submit the blob data
+fn blobs_required(blob_data: &[FieldElement], max_bytes_per_blob: u64) -> u64 {
+    state_diff_size(blob_data).div_ceil(max_bytes_per_blob)
+}
+
 /// DA word encoding:
 /// |---padding---|---class flag---|---new nonce---|---num changes---|
 ///     127 bits        1 bit           64 bits          64 bits
@@ -185,6 +238,17 @@ mod tests {
         assert_eq!(da_word, expected);
     }
 
+    #[rstest]
+    #[case(0, 131072, 0)]
+    #[case(1, 131072, 1)]
+    #[case(4096, 131072, 1)]
+    #[case(4097, 131072, 2)]
+    #[case(6, 32, 6)]
+    fn blobs_required_works(#[case] num_elements: usize, #[case] max_bytes_per_blob: u64, #[case] expected: u64) {
+        let blob_data = vec![FieldElement::ONE; num_elements];
+        assert_eq!(blobs_required(&blob_data, max_bytes_per_blob), expected);
+    }
+
     mod test_state_update_to_blob_data {
         use super::*;
         use serde_json::Error;
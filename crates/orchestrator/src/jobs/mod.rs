@@ -128,7 +128,8 @@ pub async fn verify_job(id: Uuid) -> Result<()> {
         JobVerificationStatus::Verified => {
             config.database().update_job_status(&job, JobStatus::Completed).await?;
         }
-        JobVerificationStatus::Rejected => {
+        JobVerificationStatus::Rejected(reason) => {
+            log::warn!("Verification rejected for job {}: {}", job.id, reason);
             config.database().update_job_status(&job, JobStatus::VerificationFailed).await?;
 
             // retry job processing if we haven't exceeded the max limit
@@ -143,6 +144,7 @@ pub async fn verify_job(id: Uuid) -> Result<()> {
                 return Ok(());
             } else {
                 // TODO: send alert
+                log::error!("Verification rejected for job {} and process attempts exceeded: {}", job.id, reason);
             }
         }
         JobVerificationStatus::Pending => {
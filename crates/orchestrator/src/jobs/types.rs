@@ -118,14 +118,15 @@ pub struct JobItem {
     pub version: i32,
 }
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum JobVerificationStatus {
     #[allow(dead_code)]
     Pending,
     #[allow(dead_code)]
     Verified,
+    /// Carries the reason the verification was rejected
     #[allow(dead_code)]
-    Rejected,
+    Rejected(String),
 }
 
 impl From<DaVerificationStatus> for JobVerificationStatus {
@@ -133,7 +134,7 @@ impl From<DaVerificationStatus> for JobVerificationStatus {
         match status {
             DaVerificationStatus::Pending => JobVerificationStatus::Pending,
             DaVerificationStatus::Verified => JobVerificationStatus::Verified,
-            DaVerificationStatus::Rejected => JobVerificationStatus::Rejected,
+            DaVerificationStatus::Rejected(reason) => JobVerificationStatus::Rejected(reason),
         }
     }
 }
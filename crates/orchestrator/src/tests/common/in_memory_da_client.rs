@@ -0,0 +1,145 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use async_trait::async_trait;
+use color_eyre::eyre::eyre;
+use color_eyre::Result;
+use da_client_interface::{state_diff_size, DaClient, DaSubmission, DaVerificationStatus};
+use starknet::core::types::FieldElement;
+
+/// A DA client which keeps everything in memory. Unlike `MockDaClient` it behaves like a
+/// real DA layer: published state diffs are stored under generated external ids
+/// (`in-memory-0`, `in-memory-1`, ...) and verification follows a per-blob script.
+/// Every call is recorded so tests can assert how the orchestrator used the client.
+/// Clones share their state, so a test can hand one clone to the config and keep
+/// another to script and inspect it.
+#[derive(Clone)]
+pub struct InMemoryDaClient {
+    state: Arc<Mutex<State>>,
+    max_bytes_per_blob: u64,
+    max_blob_per_txn: u64,
+}
+
+#[derive(Default)]
+struct State {
+    blobs: Vec<Vec<FieldElement>>,
+    publish_failures: VecDeque<String>,
+    publish_calls: usize,
+    submissions: Vec<DaSubmission>,
+    scripts: HashMap<String, VecDeque<Result<DaVerificationStatus, String>>>,
+    verify_calls: Vec<String>,
+}
+
+impl Default for InMemoryDaClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl InMemoryDaClient {
+    /// Create a client with no blob size limits
+    pub fn new() -> Self {
+        Self { state: Arc::new(Mutex::new(State::default())), max_bytes_per_blob: u64::MAX, max_blob_per_txn: u64::MAX }
+    }
+
+    /// Set the limits reported by `max_bytes_per_blob` and `max_blob_per_txn`
+    pub fn with_blob_limits(mut self, max_bytes_per_blob: u64, max_blob_per_txn: u64) -> Self {
+        self.max_bytes_per_blob = max_bytes_per_blob;
+        self.max_blob_per_txn = max_blob_per_txn;
+        self
+    }
+
+    /// The external id the n-th successful publish will return
+    pub fn external_id(index: usize) -> String {
+        format!("in-memory-{}", index)
+    }
+
+    fn index_of(external_id: &str) -> Option<usize> {
+        external_id.strip_prefix("in-memory-")?.parse::<usize>().ok()
+    }
+
+    /// Make the next publish fail with the given message. Can be called multiple times
+    /// to fail several publishes in a row.
+    pub fn fail_next_publish(&self, message: &str) {
+        self.state().publish_failures.push_back(message.to_string());
+    }
+
+    /// Script the statuses returned by `verify_inclusion` for an external id, one per call.
+    /// The last status keeps being returned once the script is exhausted. Blobs without a
+    /// script are `Verified`.
+    pub fn script_verification(&self, external_id: &str, statuses: Vec<DaVerificationStatus>) {
+        self.state().scripts.entry(external_id.to_string()).or_default().extend(statuses.into_iter().map(Ok));
+    }
+
+    /// Append a failure with the given message to the verification script of an external id
+    pub fn fail_verification(&self, external_id: &str, message: &str) {
+        self.state().scripts.entry(external_id.to_string()).or_default().push_back(Err(message.to_string()));
+    }
+
+    /// Returns the state diff stored under an external id
+    pub fn stored_state_diff(&self, external_id: &str) -> Option<Vec<FieldElement>> {
+        self.state().blobs.get(Self::index_of(external_id)?).cloned()
+    }
+
+    /// Number of times `publish_state_diff` was called, including failed calls
+    pub fn publish_calls(&self) -> usize {
+        self.state().publish_calls
+    }
+
+    /// Submissions returned by successful publishes, in call order
+    pub fn submissions(&self) -> Vec<DaSubmission> {
+        self.state().submissions.clone()
+    }
+
+    /// External ids passed to `verify_inclusion`, in call order
+    pub fn verify_calls(&self) -> Vec<String> {
+        self.state().verify_calls.clone()
+    }
+
+    fn state(&self) -> MutexGuard<'_, State> {
+        self.state.lock().expect("in-memory DA client state poisoned")
+    }
+}
+
+#[async_trait]
+impl DaClient for InMemoryDaClient {
+    async fn publish_state_diff(&self, state_diff: Vec<FieldElement>) -> Result<DaSubmission> {
+        let mut state = self.state();
+        state.publish_calls += 1;
+        if let Some(message) = state.publish_failures.pop_front() {
+            return Err(eyre!(message));
+        }
+        let payload_size = state_diff_size(&state_diff);
+        state.blobs.push(state_diff);
+        let index = state.blobs.len() - 1;
+        let submission = DaSubmission::new("in-memory", Self::external_id(index), payload_size)
+            .with_details(serde_json::json!({ "index": index }));
+        state.submissions.push(submission.clone());
+        Ok(submission)
+    }
+
+    async fn verify_inclusion(&self, external_id: &str) -> Result<DaVerificationStatus> {
+        let mut state = self.state();
+        state.verify_calls.push(external_id.to_string());
+
+        if let Some(script) = state.scripts.get_mut(external_id) {
+            let next = if script.len() > 1 { script.pop_front() } else { script.front().cloned() };
+            if let Some(next) = next {
+                return next.map_err(|message| eyre!(message));
+            }
+        }
+
+        match Self::index_of(external_id) {
+            Some(index) if index < state.blobs.len() => Ok(DaVerificationStatus::Verified),
+            _ => Err(eyre!("Unknown external id {}", external_id)),
+        }
+    }
+
+    fn max_bytes_per_blob(&self) -> u64 {
+        self.max_bytes_per_blob
+    }
+
+    fn max_blob_per_txn(&self) -> u64 {
+        self.max_blob_per_txn
+    }
+}
@@ -1,4 +1,5 @@
 pub mod constants;
+pub mod in_memory_da_client;
 
 use constants::*;
 use rstest::*;
@@ -14,7 +15,7 @@ use crate::{
 use crate::database::MockDatabase;
 use crate::queue::MockQueueProvider;
 use ::uuid::Uuid;
-use da_client_interface::MockDaClient;
+use da_client_interface::{DaClient, MockDaClient};
 use starknet::providers::jsonrpc::HttpTransport;
 use starknet::providers::JsonRpcClient;
 
@@ -24,19 +25,19 @@ pub async fn init_config(
     rpc_url: Option<String>,
     database: Option<MockDatabase>,
     queue: Option<MockQueueProvider>,
-    da_client: Option<MockDaClient>,
+    da_client: Option<Box<dyn DaClient>>,
 ) -> Config {
     let _ = tracing_subscriber::fmt().with_max_level(tracing::Level::INFO).with_target(false).try_init();
 
     let rpc_url = rpc_url.unwrap_or(MADARA_RPC_URL.to_string());
     let database = database.unwrap_or_default();
     let queue = queue.unwrap_or_default();
-    let da_client = da_client.unwrap_or_default();
+    let da_client = da_client.unwrap_or_else(|| Box::new(MockDaClient::default()));
 
     // init starknet client
     let provider = JsonRpcClient::new(HttpTransport::new(Url::parse(rpc_url.as_str()).expect("Failed to parse URL")));
 
-    Config::new(Arc::new(provider), Box::new(da_client), Box::new(database), Box::new(queue))
+    Config::new(Arc::new(provider), da_client, Box::new(database), Box::new(queue))
 }
 
 #[fixture]
@@ -6,16 +6,21 @@ use std::collections::HashMap;
 use httpmock::prelude::*;
 use serde_json::json;
 
+use super::super::common::in_memory_da_client::InMemoryDaClient;
 use super::super::common::{default_job_item, init_config};
 use starknet_core::types::{FieldElement, MaybePendingStateUpdate, StateDiff};
 use uuid::Uuid;
 
+use crate::config::Config;
 use crate::jobs::types::ExternalId;
 use crate::jobs::{
     da_job::DaJob,
-    types::{JobItem, JobStatus, JobType},
+    types::{JobItem, JobStatus, JobType, JobVerificationStatus},
     Job,
 };
+use da_client_interface::composite::{
+    Backend, CompositeDaClient, CompositeDaConfig, CompositeMode, PartialPublishError,
+};
 use da_client_interface::{DaVerificationStatus, MockDaClient};
 
 #[rstest]
@@ -41,20 +46,37 @@ async fn test_verify_job(#[from(default_job_item)] job_item: JobItem) {
     let mut da_client = MockDaClient::new();
     da_client.expect_verify_inclusion().times(1).returning(|_| Ok(DaVerificationStatus::Verified));
 
-    let config = init_config(None, None, None, Some(da_client)).await;
+    let config = init_config(None, None, None, Some(Box::new(da_client))).await;
     assert!(DaJob.verify_job(&config, &job_item).await.is_ok());
 }
 
 #[rstest]
 #[tokio::test]
-async fn test_process_job() {
-    let server = MockServer::start();
+async fn test_verify_job_follows_da_status(#[from(default_job_item)] job_item: JobItem) {
+    let da_client = InMemoryDaClient::new();
+    da_client.script_verification("0", vec![DaVerificationStatus::Pending, DaVerificationStatus::Verified]);
 
-    let mut da_client = MockDaClient::new();
-    let internal_id = "1";
-    da_client.expect_publish_state_diff().times(1).returning(|_| Ok(internal_id.to_string()));
-    let config = init_config(Some(format!("http://localhost:{}", server.port())), None, None, Some(da_client)).await;
+    let config = init_config(None, None, None, Some(Box::new(da_client.clone()))).await;
+    assert_eq!(DaJob.verify_job(&config, &job_item).await.unwrap(), JobVerificationStatus::Pending);
+    assert_eq!(DaJob.verify_job(&config, &job_item).await.unwrap(), JobVerificationStatus::Verified);
+    assert_eq!(da_client.verify_calls(), vec!["0".to_string(), "0".to_string()]);
+}
+
+#[rstest]
+#[tokio::test]
+async fn test_verify_job_keeps_rejection_reason(#[from(default_job_item)] job_item: JobItem) {
+    let da_client = InMemoryDaClient::new();
+    da_client.script_verification("0", vec![DaVerificationStatus::Rejected("blob failed in quorum 0".to_string())]);
 
+    let config = init_config(None, None, None, Some(Box::new(da_client))).await;
+    assert_eq!(
+        DaJob.verify_job(&config, &job_item).await.unwrap(),
+        JobVerificationStatus::Rejected("blob failed in quorum 0".to_string())
+    );
+}
+
+/// Serves an empty state update for every `starknet_getStateUpdate` request
+fn mock_empty_state_update(server: &MockServer) -> httpmock::Mock<'_> {
     let state_update = MaybePendingStateUpdate::Update(StateUpdate {
         block_hash: FieldElement::default(),
         new_root: FieldElement::default(),
@@ -71,29 +93,141 @@ async fn test_process_job() {
     let state_update = serde_json::to_value(&state_update).unwrap();
     let response = json!({ "id": 1,"jsonrpc":"2.0","result": state_update });
 
-    let state_update_mock = server.mock(|when, then| {
+    server.mock(|when, then| {
         when.path("/").body_contains("starknet_getStateUpdate");
         then.status(200).body(serde_json::to_vec(&response).unwrap());
-    });
+    })
+}
 
-    assert_eq!(
-        DaJob
-            .process_job(
-                &config,
-                &JobItem {
-                    id: Uuid::default(),
-                    internal_id: internal_id.to_string(),
-                    job_type: JobType::DataSubmission,
-                    status: JobStatus::Created,
-                    external_id: ExternalId::String("1".to_string().into_boxed_str()),
-                    metadata: HashMap::default(),
-                    version: 0,
-                }
-            )
-            .await
-            .unwrap(),
-        internal_id
-    );
+/// Config fetching blocks from `server` and publishing to `da_client`
+async fn process_job_config(server: &MockServer, da_client: &InMemoryDaClient) -> Config {
+    init_config(Some(format!("http://localhost:{}", server.port())), None, None, Some(Box::new(da_client.clone())))
+        .await
+}
+
+fn process_job_item() -> JobItem {
+    JobItem {
+        id: Uuid::default(),
+        internal_id: "1".to_string(),
+        job_type: JobType::DataSubmission,
+        status: JobStatus::Created,
+        external_id: ExternalId::String("1".to_string().into_boxed_str()),
+        metadata: HashMap::default(),
+        version: 0,
+    }
+}
+
+#[rstest]
+#[tokio::test]
+async fn test_process_job() {
+    let server = MockServer::start();
+    let state_update_mock = mock_empty_state_update(&server);
+    let da_client = InMemoryDaClient::new().with_blob_limits(131072, 6);
+    let config = process_job_config(&server, &da_client).await;
+
+    assert_eq!(DaJob.process_job(&config, &process_job_item()).await.unwrap(), InMemoryDaClient::external_id(0));
+    assert_eq!(da_client.publish_calls(), 1);
+    let published = da_client.stored_state_diff(&InMemoryDaClient::external_id(0)).unwrap();
+    assert_eq!(published.len(), 6, "empty state diff should encode to 6 field elements");
+    let submissions = da_client.submissions();
+    assert_eq!(submissions.len(), 1);
+    assert_eq!(submissions[0].external_id(), InMemoryDaClient::external_id(0));
+    assert_eq!(submissions[0].backend, "in-memory");
+    assert_eq!(submissions[0].payload_size, 6 * 32);
+    assert!(submissions[0].submitted_at_ms > 0, "submission timestamp should be set");
+    assert_eq!(submissions[0].details, Some(json!({ "index": 0 })));
 
     state_update_mock.assert();
 }
+
+#[rstest]
+#[tokio::test]
+async fn test_process_job_fails_when_blob_limit_exceeded() {
+    let server = MockServer::start();
+    let state_update_mock = mock_empty_state_update(&server);
+    // one field element per blob, an empty state diff still encodes to 6 field elements
+    let da_client = InMemoryDaClient::new().with_blob_limits(32, 1);
+    let config = process_job_config(&server, &da_client).await;
+
+    let result = DaJob.process_job(&config, &process_job_item()).await;
+
+    let error = result.expect_err("process_job should fail when the blob limit is exceeded").to_string();
+    assert!(error.contains("needs 6 blobs"), "unexpected error: {}", error);
+    assert!(error.contains("at most 1 per transaction"), "unexpected error: {}", error);
+    assert_eq!(da_client.publish_calls(), 0, "nothing should be published");
+
+    state_update_mock.assert();
+}
+
+#[rstest]
+#[tokio::test]
+async fn test_process_job_returns_publish_error() {
+    let server = MockServer::start();
+    let _state_update_mock = mock_empty_state_update(&server);
+    let da_client = InMemoryDaClient::new();
+    da_client.fail_next_publish("dispersal timed out");
+    let config = process_job_config(&server, &da_client).await;
+
+    let error = DaJob.process_job(&config, &process_job_item()).await.expect_err("publish should fail").to_string();
+    assert!(error.contains("dispersal timed out"), "unexpected error: {}", error);
+    assert_eq!(da_client.publish_calls(), 1);
+    assert!(da_client.submissions().is_empty(), "a failed publish should not store a submission");
+}
+
+#[rstest]
+#[tokio::test]
+async fn test_verify_job_after_process_job() {
+    let server = MockServer::start();
+    let _state_update_mock = mock_empty_state_update(&server);
+    let da_client = InMemoryDaClient::new();
+    let config = process_job_config(&server, &da_client).await;
+
+    let mut job_item = process_job_item();
+    let external_id = DaJob.process_job(&config, &job_item).await.unwrap();
+    job_item.external_id = ExternalId::String(external_id.clone().into_boxed_str());
+    da_client.script_verification(&external_id, vec![DaVerificationStatus::Pending, DaVerificationStatus::Verified]);
+
+    assert_eq!(DaJob.verify_job(&config, &job_item).await.unwrap(), JobVerificationStatus::Pending);
+    assert_eq!(DaJob.verify_job(&config, &job_item).await.unwrap(), JobVerificationStatus::Verified);
+    assert_eq!(da_client.verify_calls(), vec![external_id.clone(), external_id]);
+}
+
+#[rstest]
+#[tokio::test]
+async fn test_verify_job_returns_verification_error() {
+    let server = MockServer::start();
+    let _state_update_mock = mock_empty_state_update(&server);
+    let da_client = InMemoryDaClient::new();
+    let config = process_job_config(&server, &da_client).await;
+
+    let mut job_item = process_job_item();
+    let external_id = DaJob.process_job(&config, &job_item).await.unwrap();
+    job_item.external_id = ExternalId::String(external_id.clone().into_boxed_str());
+    da_client.fail_verification(&external_id, "DA node unreachable");
+
+    let error = DaJob.verify_job(&config, &job_item).await.expect_err("verification should fail").to_string();
+    assert!(error.contains("DA node unreachable"), "unexpected error: {}", error);
+}
+
+#[rstest]
+#[tokio::test]
+async fn test_process_job_reports_partial_mirror_publish() {
+    let server = MockServer::start();
+    let _state_update_mock = mock_empty_state_update(&server);
+    let primary = InMemoryDaClient::new();
+    let secondary = InMemoryDaClient::new();
+    secondary.fail_next_publish("secondary down");
+    let da_client = CompositeDaClient::new(
+        CompositeDaConfig { mode: CompositeMode::Mirror },
+        Box::new(primary.clone()),
+        Box::new(secondary),
+    );
+    let config =
+        init_config(Some(format!("http://localhost:{}", server.port())), None, None, Some(Box::new(da_client))).await;
+
+    let error = DaJob.process_job(&config, &process_job_item()).await.expect_err("publish should fail");
+    let partial = error.downcast_ref::<PartialPublishError>().expect("partial publish should reach the caller");
+    assert_eq!(partial.published_to, Backend::Primary);
+    assert_eq!(partial.submission.external_id(), InMemoryDaClient::external_id(0));
+    assert!(primary.stored_state_diff(&InMemoryDaClient::external_id(0)).is_some());
+}
@@ -1,5 +1,11 @@
+use std::time::Duration;
+
+use color_eyre::eyre::eyre;
 use color_eyre::Result;
 
+const ACCEPTED_DURATION_FORMATS: &str =
+    "a number followed by a unit (ms, s, m, h), e.g. 500ms, 2s or 1m30s, or a bare number of milliseconds";
+
 pub fn get_env_var(key: &str) -> Result<String> {
     std::env::var(key).map_err(|e| e.into())
 }
@@ -11,3 +17,155 @@ pub fn get_env_var_or_panic(key: &str) -> String {
 pub fn get_env_var_or_default(key: &str, default: &str) -> String {
     get_env_var(key).unwrap_or(default.to_string())
 }
+
+/// Reads a duration from the env var `key`, falling back to the legacy `{key}_MS` variable
+/// (a bare number of milliseconds) and then to `default`. See [`parse_duration`] for the format.
+pub fn get_env_duration_or_default(key: &str, default: &str) -> Result<Duration> {
+    let legacy_key = format!("{}_MS", key);
+    let value = match (get_env_var(key), get_env_var(&legacy_key)) {
+        (Ok(value), _) => value,
+        (Err(_), Ok(millis)) => {
+            return millis.trim().parse::<u64>().map(Duration::from_millis).map_err(|_| {
+                eyre!(
+                    "Invalid duration {:?} for env var {}: expected a bare number of milliseconds",
+                    millis,
+                    legacy_key
+                )
+            });
+        }
+        (Err(_), Err(_)) => {
+            return parse_duration(default).map_err(|e| {
+                eyre!(
+                    "Invalid default duration {:?}, used as neither {} nor {} is set: {}",
+                    default,
+                    key,
+                    legacy_key,
+                    e
+                )
+            });
+        }
+    };
+    parse_duration(&value).map_err(|e| {
+        eyre!("Invalid duration {:?} for env var {}: {}. Expected {}", value, key, e, ACCEPTED_DURATION_FORMATS)
+    })
+}
+
+/// Parses a duration such as `500ms`, `2s` or `1m30s`. A bare number is interpreted as
+/// milliseconds for backward compatibility with the `_MS` env vars.
+pub fn parse_duration(value: &str) -> Result<Duration> {
+    let value = value.trim();
+    if value.is_empty() {
+        return Err(eyre!("duration is empty"));
+    }
+    if let Ok(millis) = value.parse::<u64>() {
+        return Ok(Duration::from_millis(millis));
+    }
+
+    let mut total = Duration::ZERO;
+    let mut rest = value;
+    while !rest.is_empty() {
+        let amount_len = rest.find(|c: char| !c.is_ascii_digit()).unwrap_or(rest.len());
+        if amount_len == 0 {
+            return Err(eyre!("expected a number at {:?}", rest));
+        }
+        let amount =
+            rest[..amount_len].parse::<u64>().map_err(|e| eyre!("invalid number {:?}: {}", &rest[..amount_len], e))?;
+        rest = &rest[amount_len..];
+
+        let unit_len = rest.find(|c: char| c.is_ascii_digit()).unwrap_or(rest.len());
+        let millis_per_unit = match &rest[..unit_len] {
+            "ms" => 1,
+            "s" => 1_000,
+            "m" => 60_000,
+            "h" => 3_600_000,
+            "" => return Err(eyre!("missing unit after {}", amount)),
+            unit => return Err(eyre!("unknown unit {:?}", unit)),
+        };
+        rest = &rest[unit_len..];
+
+        let millis = amount.checked_mul(millis_per_unit).ok_or_else(|| eyre!("duration is too large"))?;
+        total = total.checked_add(Duration::from_millis(millis)).ok_or_else(|| eyre!("duration is too large"))?;
+    }
+
+    Ok(total)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_duration_units() {
+        assert_eq!(parse_duration("500ms").unwrap(), Duration::from_millis(500));
+        assert_eq!(parse_duration("2s").unwrap(), Duration::from_secs(2));
+        assert_eq!(parse_duration("3m").unwrap(), Duration::from_secs(180));
+        assert_eq!(parse_duration("1h").unwrap(), Duration::from_secs(3600));
+    }
+
+    #[test]
+    fn parse_duration_compound() {
+        assert_eq!(parse_duration("1m30s").unwrap(), Duration::from_secs(90));
+        assert_eq!(parse_duration("1h2m3s4ms").unwrap(), Duration::from_millis(3_723_004));
+        assert_eq!(parse_duration(" 2s500ms ").unwrap(), Duration::from_millis(2500));
+    }
+
+    #[test]
+    fn parse_duration_bare_integer_is_millis() {
+        assert_eq!(parse_duration("1000").unwrap(), Duration::from_secs(1));
+        assert_eq!(parse_duration("0").unwrap(), Duration::ZERO);
+    }
+
+    #[test]
+    fn parse_duration_rejects_invalid() {
+        for value in ["", "s", "10x", "1.5s", "-1s", "1s2", "ms10", "99999999999999999999h"] {
+            assert!(parse_duration(value).is_err(), "{:?} should not parse", value);
+        }
+    }
+
+    #[test]
+    fn env_duration_reads_new_variable() {
+        std::env::set_var("UTILS_TEST_NEW_DELAY", "1m30s");
+        assert_eq!(get_env_duration_or_default("UTILS_TEST_NEW_DELAY", "1s").unwrap(), Duration::from_secs(90));
+    }
+
+    #[test]
+    fn env_duration_falls_back_to_legacy_ms_variable() {
+        std::env::set_var("UTILS_TEST_LEGACY_DELAY_MS", "1500");
+        assert_eq!(get_env_duration_or_default("UTILS_TEST_LEGACY_DELAY", "1s").unwrap(), Duration::from_millis(1500));
+    }
+
+    #[test]
+    fn env_duration_prefers_new_variable_over_legacy() {
+        std::env::set_var("UTILS_TEST_BOTH_DELAY", "2s");
+        std::env::set_var("UTILS_TEST_BOTH_DELAY_MS", "5000");
+        assert_eq!(get_env_duration_or_default("UTILS_TEST_BOTH_DELAY", "1s").unwrap(), Duration::from_secs(2));
+    }
+
+    #[test]
+    fn env_duration_uses_default() {
+        assert_eq!(get_env_duration_or_default("UTILS_TEST_UNSET_DELAY", "250ms").unwrap(), Duration::from_millis(250));
+    }
+
+    #[test]
+    fn env_duration_error_names_variable() {
+        std::env::set_var("UTILS_TEST_BAD_DELAY", "soon");
+        let error = get_env_duration_or_default("UTILS_TEST_BAD_DELAY", "1s").unwrap_err().to_string();
+        assert!(error.contains("UTILS_TEST_BAD_DELAY"), "unexpected error: {}", error);
+        assert!(error.contains("1m30s"), "unexpected error: {}", error);
+    }
+
+    #[test]
+    fn env_duration_legacy_variable_only_accepts_millis() {
+        std::env::set_var("UTILS_TEST_UNIT_DELAY_MS", "2s");
+        let error = get_env_duration_or_default("UTILS_TEST_UNIT_DELAY", "1s").unwrap_err().to_string();
+        assert!(error.contains("UTILS_TEST_UNIT_DELAY_MS"), "unexpected error: {}", error);
+        assert!(error.contains("milliseconds"), "unexpected error: {}", error);
+    }
+
+    #[test]
+    fn env_duration_bad_default_is_reported_as_default() {
+        let error = get_env_duration_or_default("UTILS_TEST_BAD_DEFAULT_DELAY", "soon").unwrap_err().to_string();
+        assert!(error.contains("default"), "unexpected error: {}", error);
+        assert!(error.contains("neither"), "unexpected error: {}", error);
+    }
+}
@@ -1 +1,2 @@
 pub mod env_utils;
+pub mod retry;
@@ -0,0 +1,219 @@
+use std::future::Future;
+use std::time::Duration;
+
+use rand::Rng;
+
+/// Exponential backoff policy used by [`retry_with_policy`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct RetryPolicy {
+    /// Maximum number of attempts, including the first one. 0 is treated as 1.
+    pub max_attempts: u32,
+    /// Delay before the first retry
+    pub initial_delay: Duration,
+    /// Upper bound for the delay between two attempts, before jitter
+    pub max_delay: Duration,
+    /// Factor the delay is multiplied by after every retry. A negative or NaN multiplier is
+    /// treated as 1, i.e. a constant delay.
+    pub multiplier: f64,
+    /// Fraction of the delay that is randomised, e.g. 0.1 sleeps between 90% and 110% of the delay
+    pub jitter: f64,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_delay: Duration::from_secs(1),
+            max_delay: Duration::from_secs(30),
+            multiplier: 2.0,
+            jitter: 0.0,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Returns the delay to wait after the given failed attempt (starting at 1), without jitter
+    pub fn delay_for_attempt(&self, attempt: u32) -> Duration {
+        if self.initial_delay.is_zero() {
+            return Duration::ZERO;
+        }
+        let exponent = i32::try_from(attempt.saturating_sub(1)).unwrap_or(i32::MAX);
+        let multiplier = if self.multiplier.is_nan() || self.multiplier < 0.0 { 1.0 } else { self.multiplier };
+        let secs = self.initial_delay.as_secs_f64() * multiplier.powi(exponent);
+        if !secs.is_finite() {
+            return self.max_delay;
+        }
+        Duration::try_from_secs_f64(secs).unwrap_or(self.max_delay).min(self.max_delay)
+    }
+
+    fn jittered(&self, delay: Duration) -> Duration {
+        if self.jitter.is_nan() || self.jitter <= 0.0 {
+            return delay;
+        }
+        let jitter = self.jitter.min(1.0);
+        let factor = rand::thread_rng().gen_range(1.0 - jitter..=1.0 + jitter);
+        Duration::try_from_secs_f64(delay.as_secs_f64() * factor).unwrap_or(Duration::MAX)
+    }
+}
+
+/// Runs `op` until it succeeds, returns an error for which `is_retryable` is false, or
+/// `policy.max_attempts` is reached, sleeping according to the policy between attempts.
+/// The error of the last attempt is returned.
+pub async fn retry_with_policy<T, E, F, Fut, R>(policy: &RetryPolicy, mut is_retryable: R, mut op: F) -> Result<T, E>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+    R: FnMut(&E) -> bool,
+{
+    let mut attempt = 1;
+    loop {
+        match op().await {
+            Ok(value) => return Ok(value),
+            Err(e) if attempt < policy.max_attempts && is_retryable(&e) => {
+                tokio::time::sleep(policy.jittered(policy.delay_for_attempt(attempt))).await;
+                attempt += 1;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use tokio::time::Instant;
+
+    use super::*;
+
+    fn policy(max_attempts: u32) -> RetryPolicy {
+        RetryPolicy {
+            max_attempts,
+            initial_delay: Duration::from_millis(100),
+            max_delay: Duration::from_millis(300),
+            multiplier: 2.0,
+            jitter: 0.0,
+        }
+    }
+
+    /// Runs `retry_with_policy` with an op that fails `failures` times and returns the
+    /// result together with the offsets at which each attempt started
+    async fn run(policy: &RetryPolicy, failures: usize) -> (Result<usize, String>, Vec<Duration>) {
+        let start = Instant::now();
+        let attempts = Arc::new(Mutex::new(Vec::new()));
+        let result = retry_with_policy(
+            policy,
+            |_: &String| true,
+            || {
+                let attempts = attempts.clone();
+                async move {
+                    let mut attempts = attempts.lock().unwrap();
+                    attempts.push(start.elapsed());
+                    if attempts.len() <= failures {
+                        Err(format!("attempt {} failed", attempts.len()))
+                    } else {
+                        Ok(attempts.len())
+                    }
+                }
+            },
+        )
+        .await;
+        let attempts = attempts.lock().unwrap().clone();
+        (result, attempts)
+    }
+
+    #[test]
+    fn delay_for_attempt_grows_and_caps() {
+        let policy = policy(10);
+        assert_eq!(policy.delay_for_attempt(1), Duration::from_millis(100));
+        assert_eq!(policy.delay_for_attempt(2), Duration::from_millis(200));
+        assert_eq!(policy.delay_for_attempt(3), Duration::from_millis(300));
+        assert_eq!(policy.delay_for_attempt(u32::MAX), Duration::from_millis(300));
+    }
+
+    #[test]
+    fn invalid_multiplier_gives_constant_delay() {
+        for multiplier in [-2.0, f64::NAN] {
+            let policy = RetryPolicy { multiplier, ..policy(10) };
+            for attempt in 1..5 {
+                assert_eq!(policy.delay_for_attempt(attempt), Duration::from_millis(100), "multiplier {}", multiplier);
+            }
+        }
+
+        let infinite = RetryPolicy { multiplier: f64::INFINITY, ..policy(10) };
+        assert_eq!(infinite.delay_for_attempt(1), Duration::from_millis(100));
+        assert_eq!(infinite.delay_for_attempt(2), Duration::from_millis(300));
+
+        // a policy without delay never sleeps, even once the multiplier overflows
+        for multiplier in [2.0, f64::INFINITY, -2.0, f64::NAN] {
+            let no_delay = RetryPolicy { initial_delay: Duration::ZERO, multiplier, ..policy(10) };
+            for attempt in [1, 2, 1025, u32::MAX] {
+                assert_eq!(no_delay.delay_for_attempt(attempt), Duration::ZERO, "multiplier {}", multiplier);
+            }
+        }
+    }
+
+    #[test]
+    fn jitter_saturates_instead_of_overflowing() {
+        let policy = RetryPolicy { max_delay: Duration::MAX, jitter: 0.5, ..policy(2) };
+        for _ in 0..50 {
+            assert!(policy.jittered(Duration::MAX) >= Duration::MAX / 4);
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn succeeds_first_time_without_sleeping() {
+        let (result, attempts) = run(&policy(5), 0).await;
+        assert_eq!(result, Ok(1));
+        assert_eq!(attempts, vec![Duration::ZERO]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn follows_backoff_schedule() {
+        let (result, attempts) = run(&policy(5), 3).await;
+        assert_eq!(result, Ok(4));
+        let expected = [0, 100, 300, 600].map(Duration::from_millis);
+        assert_eq!(attempts, expected);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn returns_last_error_when_attempts_exhausted() {
+        let (result, attempts) = run(&policy(3), 10).await;
+        assert_eq!(result, Err("attempt 3 failed".to_string()));
+        assert_eq!(attempts.len(), 3);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn zero_max_attempts_runs_once() {
+        let (result, attempts) = run(&policy(0), 10).await;
+        assert_eq!(result, Err("attempt 1 failed".to_string()));
+        assert_eq!(attempts.len(), 1);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn does_not_retry_permanent_errors() {
+        let mut calls = 0;
+        let result: Result<(), &str> = retry_with_policy(
+            &policy(5),
+            |e: &&str| *e != "permanent",
+            || {
+                calls += 1;
+                async { Err("permanent") }
+            },
+        )
+        .await;
+        assert_eq!(result, Err("permanent"));
+        assert_eq!(calls, 1);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn jitter_stays_within_bounds() {
+        let policy = RetryPolicy { jitter: 0.5, ..policy(2) };
+        for _ in 0..50 {
+            let (_, attempts) = run(&policy, 1).await;
+            let delay = attempts[1] - attempts[0];
+            assert!(delay >= Duration::from_millis(50), "delay {:?} below jitter bound", delay);
+            assert!(delay <= Duration::from_millis(150), "delay {:?} above jitter bound", delay);
+        }
+    }
+}